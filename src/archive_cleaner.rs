@@ -2,79 +2,150 @@ use image::{
     codecs::webp::WebPEncoder, imageops::FilterType, DynamicImage, ExtendedColorType, GenericImage,
     GenericImageView,
 };
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use crossbeam_channel::Sender;
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
 use zip::write::SimpleFileOptions;
 use zip::{read::ZipArchive, write::ZipWriter};
 
+use crate::cache::{self, CachedArchiveType};
 use crate::file_handler::extract_file_info;
 use crate::file_handler::IMAGE_SIZE;
+use crate::image_decode;
+use crate::progress::{self, ProgressData, StopFlag};
+
+/// Resolve how many worker threads the encode pool should use: `thread_count` if the caller
+/// pinned one, otherwise the number of available cores (falling back to 1).
+pub fn get_number_of_threads(thread_count: Option<usize>) -> usize {
+    thread_count.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    })
+}
 
+/// Encode every page in parallel on a bounded rayon pool, then write the encoded pages to the
+/// zip in index order on a single thread so the `ZipWriter` is never contended.
 fn process_images_threaded(
-    cleaner: Arc<ArchiveCleaner>, 
-    images: Vec<DynamicImage>, 
-    shared_zip: Arc<Mutex<ZipWriter<File>>>,
-    pb: ProgressBar,
-) {
-    let multi_progress = MultiProgress::new();
-    let total_images = images.len();
-    let mut handles = vec![];
-
-    for (index, image) in images.into_iter().enumerate() {
-        let image = image.clone();
-        let pb = pb.clone();
-        let spinner = multi_progress.add(ProgressBar::new_spinner());
-        spinner.set_style(
-            ProgressStyle::with_template("{prefix:.bold.dim} {spinner} {wide_msg}")
-                .unwrap()
-                .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈ "),
-        );
-        spinner.enable_steady_tick(Duration::from_millis(100));
-        spinner.set_prefix(format!("[{}/{}]", index + 1, total_images));
-
-        // Clone necessary variables into the thread
-        let cleaner = Arc::clone(&cleaner);
-        let shared_zip = Arc::clone(&shared_zip);
-
-        // Spawn the thread
-        let handle = thread::spawn(move || {
-            if let Err(e) = cleaner.process_image(index, &image, shared_zip, &pb, &spinner) {
-                spinner.finish_with_message(format!("Error: {e}"));
-            }
-        });
-
-        handles.push(handle);
+    cleaner: &ArchiveCleaner,
+    images: &[DynamicImage],
+    zip_writer: &mut ZipWriter<File>,
+    pb: &ProgressBar,
+) -> io::Result<()> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(get_number_of_threads(cleaner.thread_count))
+        .build()
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Failed to build thread pool: {e}")))?;
+
+    let total = images.len();
+    let encoded: Vec<Option<Vec<u8>>> = pool.install(|| {
+        images
+            .par_iter()
+            .enumerate()
+            .map(|(index, image)| {
+                if cleaner.stopped() {
+                    return None;
+                }
+                let result = cleaner.encode_page(image);
+                pb.inc(1);
+                cleaner.emit_progress(3, 3, index + 1, total);
+                result.ok()
+            })
+            .collect()
+    });
+
+    let extension = cleaner.output_format.extension();
+    for (index, data) in encoded.into_iter().enumerate() {
+        let Some(data) = data else { continue };
+        zip_writer.start_file(
+            format!("{}.{}", index + 1, extension),
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
+        )?;
+        zip_writer.write_all(&data)?;
     }
 
-    for handle in handles {
-        handle.join().expect("Thread panicked");
-    }
     pb.finish_with_message("Processing complete!");
+    Ok(())
 }
 
+#[derive(Clone, Copy)]
 enum ArchiveType {
     Manhwa,
     Manga,
 }
 
+/// Output codec and quality for re-encoded pages.
+#[derive(Clone, Copy)]
+pub enum OutputFormat {
+    WebpLossless,
+    /// Lossy WebP at the given quality, 0-100.
+    WebpLossy(u8),
+    /// AVIF at the given quality, 0-100.
+    Avif(u8),
+}
+
+impl OutputFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::WebpLossless | OutputFormat::WebpLossy(_) => "webp",
+            OutputFormat::Avif(_) => "avif",
+        }
+    }
+}
+
+impl From<ArchiveType> for CachedArchiveType {
+    fn from(archive_type: ArchiveType) -> Self {
+        match archive_type {
+            ArchiveType::Manhwa => CachedArchiveType::Manhwa,
+            ArchiveType::Manga => CachedArchiveType::Manga,
+        }
+    }
+}
+
 struct ImageInfo {
     width: u32,
     height: u32,
 }
 
+/// A single unreadable entry found by [`ArchiveCleaner::scan_broken`].
+#[derive(Debug, Clone)]
+pub struct BrokenEntry {
+    pub path: PathBuf,
+    pub inner_name: String,
+    pub size: u64,
+    pub error_string: String,
+    pub type_of_file: String,
+}
+
+/// Default Hamming-distance threshold at or below which two pages are considered duplicates.
+pub const DEFAULT_DEDUPE_THRESHOLD: u32 = 5;
+
 pub struct ArchiveCleaner {
     archive_type: ArchiveType,
     min_image_size: ImageInfo,
     archive_path: PathBuf,
+    dedupe: bool,
+    dedupe_threshold: u32,
+    thread_count: Option<usize>,
+    force: bool,
+    output_format: OutputFormat,
+    stop: StopFlag,
+    progress_tx: Option<Sender<ProgressData>>,
 }
 
 impl ArchiveCleaner {
-    pub fn new(archive_path: &Path) -> Self {
+    pub fn new(
+        archive_path: &Path,
+        dedupe: bool,
+        dedupe_threshold: u32,
+        thread_count: Option<usize>,
+        force: bool,
+        output_format: OutputFormat,
+    ) -> Self {
         Self {
             archive_type: ArchiveType::Manga,
             min_image_size: ImageInfo {
@@ -82,36 +153,192 @@ impl ArchiveCleaner {
                 height: IMAGE_SIZE.1,
             },
             archive_path: archive_path.to_path_buf(),
+            dedupe,
+            dedupe_threshold,
+            thread_count,
+            force,
+            output_format,
+            stop: progress::new_stop_flag(),
+            progress_tx: None,
         }
     }
 
+    /// Let a caller (GUI, job runner, ...) observe progress and cooperatively cancel the run.
+    pub fn with_progress(mut self, stop: StopFlag, progress_tx: Sender<ProgressData>) -> Self {
+        self.stop = stop;
+        self.progress_tx = Some(progress_tx);
+        self
+    }
+
+    fn emit_progress(&self, current_stage: usize, max_stage: usize, items_checked: usize, items_to_check: usize) {
+        if let Some(tx) = &self.progress_tx {
+            let _ = tx.send(ProgressData {
+                current_stage,
+                max_stage,
+                items_checked,
+                items_to_check,
+            });
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        progress::is_stopped(&self.stop)
+    }
+
     pub fn clean_archive_file(mut self, max_images_to_check: usize) -> Result<(), io::Error> {
-        let images = match self.read_images_from_archive() {
+        const MAX_STAGE: usize = 3;
+        let (size, modified) = cache::archive_metadata(&self.archive_path)?;
+
+        if !self.force {
+            let cache = cache::load_cache().unwrap_or_default();
+            if cache.get(&self.archive_path, size, modified).is_some() {
+                return Ok(());
+            }
+        }
+
+        self.emit_progress(1, MAX_STAGE, 0, 1);
+        let broken = self.scan_broken()?;
+        if !broken.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Archive contains {} unreadable page(s), refusing to rewrite: {}",
+                    broken.len(),
+                    broken
+                        .iter()
+                        .map(|entry| entry.inner_name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ));
+        }
+        if self.stopped() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cleaning stopped by caller"));
+        }
+
+        self.emit_progress(2, MAX_STAGE, 0, 1);
+        let mut images = match self.read_images_from_archive() {
             Ok(imgs) => imgs,
             Err(e) => return Err(io::Error::new(io::ErrorKind::Other, format!("Failed to read images from archive: {}", e))),
         };
-    
-        if self.should_write_archive(&images, max_images_to_check) {
-            match self.write_archive(&images) {
-                Ok(_) => (),
-                Err(e) => return Err(io::Error::new(io::ErrorKind::Other, format!("Failed to write archive: {}", e))),
+        if self.stopped() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cleaning stopped by caller"));
+        }
+
+        let mut dropped_duplicates = false;
+        if self.dedupe {
+            let original_count = images.len();
+            images = self.dedupe_images(images);
+            dropped_duplicates = images.len() != original_count;
+        }
+
+        let page_hashes: Vec<u64> = images.iter().map(Self::dhash).collect();
+        let archive_path = self.archive_path.clone();
+        // Always run this (it also picks `self.archive_type`), then OR in `dropped_duplicates`:
+        // a dedupe pass that actually dropped pages must still be persisted even if no page
+        // crosses the resize-size threshold, or `--dedupe` silently no-ops on archives whose
+        // pages are all already small.
+        let needs_write =
+            self.should_write_archive(&images, max_images_to_check) || dropped_duplicates;
+        let archive_type = self.archive_type;
+
+        if needs_write {
+            self.emit_progress(3, MAX_STAGE, 0, images.len());
+            if let Err(e) = self.write_archive(&images) {
+                return Err(io::Error::new(io::ErrorKind::Other, format!("Failed to write archive: {}", e)));
             }
         }
-        
+
+        // A rewrite renames a new `<name>.cbz` into place, which for a `.cbz` input is
+        // `archive_path` itself, so its size/modified-time are no longer what we stat'd at the
+        // top of this function. Re-stat after a rewrite, or the very
+        // next run would see a "changed" file and pay for a full re-decode for nothing.
+        let (size, modified) = if needs_write {
+            cache::archive_metadata(&archive_path)?
+        } else {
+            (size, modified)
+        };
+
+        // Record this run so an unchanged archive (same size + modified-time) is skipped next
+        // time, whether or not it needed rewriting.
+        let mut cache = cache::load_cache().unwrap_or_default();
+        cache.insert(
+            &archive_path,
+            cache::CacheEntry {
+                size,
+                modified,
+                archive_type: archive_type.into(),
+                page_hashes,
+            },
+        );
+        cache::save_cache(&cache)?;
+
         Ok(())
     }
 
+    /// Scan every image entry in the archive without modifying it, reporting any page that
+    /// fails to decode or fails its stored CRC check (truncated/corrupt zip entries).
+    pub fn scan_broken(&self) -> io::Result<Vec<BrokenEntry>> {
+        let archive_file = File::open(&self.archive_path)?;
+        let mut archive = ZipArchive::new(archive_file)?;
+        let mut broken = Vec::new();
+
+        for i in 0..archive.len() {
+            if self.stopped() {
+                break;
+            }
+
+            let mut file = archive.by_index(i)?;
+            if !self.is_image(file.name()) {
+                continue;
+            }
+
+            let inner_name = file.name().to_string();
+            let size = file.size();
+            let mut file_data = Vec::new();
+
+            // Reading the entry out fully also surfaces zip's own CRC32 mismatch errors.
+            if let Err(e) = file.read_to_end(&mut file_data) {
+                broken.push(BrokenEntry {
+                    path: self.archive_path.clone(),
+                    inner_name,
+                    size,
+                    error_string: format!("Failed to read entry (possible CRC mismatch): {e}"),
+                    type_of_file: "image".to_string(),
+                });
+                continue;
+            }
+
+            if let Err(e) = image_decode::decode_page(&inner_name, &file_data) {
+                broken.push(BrokenEntry {
+                    path: self.archive_path.clone(),
+                    inner_name,
+                    size,
+                    error_string: format!("Failed to decode image: {e}"),
+                    type_of_file: "image".to_string(),
+                });
+            }
+        }
+
+        Ok(broken)
+    }
+
     fn read_images_from_archive(&self) -> Result<Vec<DynamicImage>, io::Error> {
         let archive_file = File::open(self.archive_path.clone())?;
         let mut archive = ZipArchive::new(archive_file)?;
         let mut images = Vec::new();
 
         for i in 0..archive.len() {
+            if self.stopped() {
+                break;
+            }
+
             let mut file = archive.by_index(i)?;
             if self.is_image(file.name()) {
+                let inner_name = file.name().to_string();
                 let mut file_data = Vec::new();
                 file.read_to_end(&mut file_data)?;
-                if let Ok(img) = image::load_from_memory(&file_data) {
+                if let Ok(img) = image_decode::decode_page(&inner_name, &file_data) {
                     images.push(img);
                 }
             }
@@ -119,6 +346,81 @@ impl ArchiveCleaner {
         Ok(images)
     }
 
+    /// Like [`Self::read_images_from_archive`], but also keeps each page's original entry name
+    /// and raw bytes so a dedupe-only pass can copy survivors back out byte-for-byte.
+    fn read_pages_from_archive(&self) -> Result<Vec<(String, Vec<u8>, DynamicImage)>, io::Error> {
+        let archive_file = File::open(self.archive_path.clone())?;
+        let mut archive = ZipArchive::new(archive_file)?;
+        let mut pages = Vec::new();
+
+        for i in 0..archive.len() {
+            if self.stopped() {
+                break;
+            }
+
+            let mut file = archive.by_index(i)?;
+            if self.is_image(file.name()) {
+                let inner_name = file.name().to_string();
+                let mut file_data = Vec::new();
+                file.read_to_end(&mut file_data)?;
+                if let Ok(img) = image_decode::decode_page(&inner_name, &file_data) {
+                    pages.push((inner_name, file_data, img));
+                }
+            }
+        }
+        Ok(pages)
+    }
+
+    /// Remove near-duplicate pages without resizing or re-encoding anything else: surviving
+    /// pages are copied into the rewritten archive byte-for-byte, under their original names.
+    /// Leaves the archive untouched if no page turned out to be a duplicate.
+    pub fn dedupe_archive_file(self) -> Result<(), io::Error> {
+        let broken = self.scan_broken()?;
+        if !broken.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Archive contains {} unreadable page(s), refusing to rewrite: {}",
+                    broken.len(),
+                    broken
+                        .iter()
+                        .map(|entry| entry.inner_name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ));
+        }
+        if self.stopped() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cleaning stopped by caller"));
+        }
+
+        let pages = self.read_pages_from_archive()?;
+        let original_count = pages.len();
+        let kept = self.dedupe_by_hash(pages, |(_, _, image)| Self::dhash(image));
+
+        if kept.len() == original_count {
+            return Ok(());
+        }
+
+        let (name, dir_path) = extract_file_info(&self.archive_path)?;
+        let temp_archive_path = dir_path.join(format!("{}.temp.cbz", name));
+        let mut zip_writer = ZipWriter::new(File::create(&temp_archive_path)?);
+
+        for (inner_name, data, _image) in &kept {
+            zip_writer.start_file(
+                inner_name.clone(),
+                SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
+            )?;
+            zip_writer.write_all(data)?;
+        }
+        zip_writer.finish().map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("Failed to finish zip archive: {e}"))
+        })?;
+        fs::rename(temp_archive_path, &self.archive_path)?;
+
+        Ok(())
+    }
+
     fn should_write_archive(
         &mut self,
         images: &[DynamicImage],
@@ -171,11 +473,18 @@ impl ArchiveCleaner {
         }
     }
 
-    pub fn encode_webp(&self, image: &DynamicImage) -> Result<Vec<u8>, String> {
-        // Convert the image to RGBA format
+    /// Encode an image using `self.output_format`, returning the encoded bytes.
+    pub fn encode_image(&self, image: &DynamicImage) -> Result<Vec<u8>, String> {
+        match self.output_format {
+            OutputFormat::WebpLossless => Self::encode_webp_lossless(image),
+            OutputFormat::WebpLossy(quality) => Self::encode_webp_lossy(image, quality),
+            OutputFormat::Avif(quality) => Self::encode_avif(image, quality),
+        }
+    }
+
+    fn encode_webp_lossless(image: &DynamicImage) -> Result<Vec<u8>, String> {
         let rgba_image = image.to_rgba8();
         let mut webp_data = Vec::new();
-        // Create a WebP encoder (support only lossless feature for now)
         let encoder = WebPEncoder::new_lossless(&mut webp_data);
         encoder
             .encode(
@@ -189,6 +498,25 @@ impl ArchiveCleaner {
         Ok(webp_data)
     }
 
+    fn encode_webp_lossy(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, String> {
+        let rgba_image = image.to_rgba8();
+        let encoder = webp::Encoder::from_rgba(rgba_image.as_raw(), rgba_image.width(), rgba_image.height());
+        Ok(encoder.encode(quality as f32).to_vec())
+    }
+
+    fn encode_avif(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, String> {
+        let mut avif_data = Vec::new();
+        image
+            .write_with_encoder(image::codecs::avif::AvifEncoder::new_with_speed_quality(
+                &mut avif_data,
+                6,
+                quality,
+            ))
+            .map_err(|e| format!("{e}"))?;
+
+        Ok(avif_data)
+    }
+
     fn crop_image(&self, image: &DynamicImage, y_offset: u32, slice_bottom: u32) -> DynamicImage {
         image.crop_imm(0, y_offset, image.width(), slice_bottom - y_offset)
     }
@@ -209,17 +537,24 @@ impl ArchiveCleaner {
         let num_slices = (total_height + slice_height - 1) / slice_height;
 
         for slice_index in 0..num_slices {
+            if self.stopped() {
+                drop(new_zip);
+                fs::remove_file(&temp_archive_path)?;
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "Cleaning stopped by caller"));
+            }
+
             let slice_bottom = ((slice_index + 1) * slice_height).min(total_height);
             let cropped_image =
                 self.crop_image(&combined_image, slice_index * slice_height, slice_bottom);
             let image_data = self
-                .encode_webp(&cropped_image)
+                .encode_image(&cropped_image)
                 .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
             new_zip.start_file(
-                format!("{}.webp", slice_index + 1),
+                format!("{}.{}", slice_index + 1, self.output_format.extension()),
                 SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
             )?;
             new_zip.write_all(&image_data)?;
+            self.emit_progress(3, 3, (slice_index + 1) as usize, num_slices as usize);
         }
 
         // TODO: Remove commented code later
@@ -232,81 +567,109 @@ impl ArchiveCleaner {
     fn process_non_manhwa_images(self, images: &[DynamicImage]) -> io::Result<()> {
         let (name, dir_path) = extract_file_info(&self.archive_path)?;
         let temp_archive_path = dir_path.join(format!("{}.temp.cbz", name));
-        let new_zip = ZipWriter::new(File::create(&temp_archive_path)?);
-        let shared_zip = Arc::new(Mutex::new(new_zip));
-        let total_images = images.len();
-        let pb = ProgressBar::new(total_images as u64);
+        let mut zip_writer = ZipWriter::new(File::create(&temp_archive_path)?);
+
+        let pb = ProgressBar::new(images.len() as u64);
         pb.set_style(
             ProgressStyle::with_template("{msg} {wide_bar} {pos}/{len} ({eta})")
                 .unwrap()
                 .progress_chars("=>-"),
         );
-    
-        let cleaner_arc = Arc::new(self);
-        let images_cloned: Vec<_> = images.to_vec();
-        process_images_threaded(cleaner_arc, images_cloned, shared_zip.clone(), pb);
+
+        process_images_threaded(&self, images, &mut zip_writer, &pb)?;
+
+        if self.stopped() {
+            drop(zip_writer);
+            fs::remove_file(&temp_archive_path)?;
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Cleaning stopped by caller"));
+        }
+
+        zip_writer
+            .finish()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Failed to finish zip archive: {e}")))?;
         fs::rename(temp_archive_path, dir_path.join(format!("{}.cbz", name)))?;
         Ok(())
     }
 
-    /// Process a single image, handle progress updates, write to the zip file and handle spinners.
-    fn process_image(
-        &self,
-        index: usize,
-        image: &DynamicImage,
-        zip_writer: Arc<Mutex<ZipWriter<std::fs::File>>>,
-        pb: &ProgressBar,
-        spinner: &ProgressBar,
-    ) -> Result<(), io::Error> {
-        // Lock the Mutex to get access to the ZipWriter
-        let mut zip_writer = zip_writer.lock().map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to lock ZipWriter: {}", e),
-            )
-        })?;
-
-        // Check if image meets criteria
+    /// Resize a single page and encode it in `self.output_format`. Returns an error for pages that don't meet the
+    /// minimum size criteria so the caller can skip writing them.
+    fn encode_page(&self, image: &DynamicImage) -> Result<Vec<u8>, io::Error> {
         if !self.image_meets_criteria(image.width(), image.height()) {
-            pb.inc(1); // Update overall progress even if skipped
-            spinner.finish_with_message("Skipped"); // Mark spinner as skipped
             return Err(io::Error::new(io::ErrorKind::Other, "Processing error"));
         }
 
-        // Resize image
         let resized_image = image.thumbnail(self.min_image_size.width, self.min_image_size.height);
+        self.encode_image(&resized_image)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
 
-        // Encode image in webp
-        let image_data = match self.encode_webp(&resized_image) {
-            Ok(data) => data,
-            Err(e) => {
-                pb.inc(1); // Update overall progress on failure
-                spinner.finish_with_message("Error");
-                return Err(io::Error::new(io::ErrorKind::Other, e));
+    /// Compute a 64-bit dHash (difference hash) for an image: grayscale, resize to 9x8, then
+    /// for each row set a bit where a pixel is brighter than its right-hand neighbour.
+    fn dhash(image: &DynamicImage) -> u64 {
+        let small = image
+            .resize_exact(9, 8, FilterType::Triangle)
+            .to_luma8();
+        let mut hash: u64 = 0;
+        for y in 0..8 {
+            for x in 0..8 {
+                let left = small.get_pixel(x, y).0[0];
+                let right = small.get_pixel(x + 1, y).0[0];
+                hash <<= 1;
+                if left > right {
+                    hash |= 1;
+                }
             }
-        };
+        }
+        hash
+    }
 
-        // Create the file name
-        let file_name = format!("{}.webp", index + 1);
+    /// Drop near-duplicate items (Hamming distance <= `dedupe_threshold` between `hash_of`
+    /// results), keeping the first of each duplicate group. Uses a simple O(n^2) scan, which is
+    /// fine for the page counts a single manga/manhwa archive holds.
+    fn dedupe_by_hash<T>(&self, items: Vec<T>, hash_of: impl Fn(&T) -> u64) -> Vec<T> {
+        let hashes: Vec<u64> = items.iter().map(&hash_of).collect();
+        let mut kept = Vec::with_capacity(items.len());
+        let mut kept_hashes: Vec<u64> = Vec::with_capacity(items.len());
+        let mut removed_indices = Vec::new();
+
+        for (index, (item, hash)) in items.into_iter().zip(hashes).enumerate() {
+            let is_duplicate = kept_hashes
+                .iter()
+                .any(|kept_hash| (kept_hash ^ hash).count_ones() <= self.dedupe_threshold);
+
+            if is_duplicate {
+                removed_indices.push(index);
+                continue;
+            }
 
-        // Start new file in the zip archive
-        zip_writer.start_file(
-            file_name,
-            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored),
-        )?;
+            kept_hashes.push(hash);
+            kept.push(item);
+        }
+
+        if !removed_indices.is_empty() {
+            let pb = ProgressBar::new(removed_indices.len() as u64);
+            pb.set_style(
+                ProgressStyle::with_template("{msg} {wide_bar} {pos}/{len}")
+                    .unwrap()
+                    .progress_chars("=>-"),
+            );
+            pb.set_message("Removing duplicate pages");
+            for index in &removed_indices {
+                pb.set_message(format!("Removed duplicate page {}", index + 1));
+                pb.inc(1);
+            }
+            pb.finish_with_message(format!("Removed {} duplicate page(s)", removed_indices.len()));
+        }
 
-        // Write image data to zip file
-        zip_writer.write_all(&image_data)?;
+        kept
+    }
 
-        pb.inc(1); // Update the main progress bar after each image
-        spinner.finish_with_message("Done!"); // Mark the spinner as finished
-        Ok(())
+    /// Drop near-duplicate pages, keeping the first page of each duplicate group.
+    fn dedupe_images(&self, images: Vec<DynamicImage>) -> Vec<DynamicImage> {
+        self.dedupe_by_hash(images, Self::dhash)
     }
 
     fn is_image(&self, file_name: &str) -> bool {
-        file_name.ends_with(".webp")
-            || file_name.ends_with(".jpg")
-            || file_name.ends_with(".jpeg")
-            || file_name.ends_with(".png")
+        image_decode::is_image(file_name)
     }
 }
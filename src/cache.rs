@@ -0,0 +1,87 @@
+//! Disk cache of previously-cleaned archives, keyed by path + size + modified-time, so
+//! `ArchiveCleaner::clean_archive_file` can skip an archive it already processed successfully
+//! on a prior run instead of re-decoding and re-encoding every page.
+
+use directories_next::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const CACHE_FILE_NAME: &str = "archive_cache.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CachedArchiveType {
+    Manhwa,
+    Manga,
+}
+
+/// What a successful `clean_archive_file` run recorded about an archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub size: u64,
+    pub modified: u64,
+    pub archive_type: CachedArchiveType,
+    pub page_hashes: Vec<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ArchiveCache {
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl ArchiveCache {
+    /// Look up a cached entry, returning it only if the archive's size and modified-time
+    /// still match what was recorded (i.e. the file hasn't changed since).
+    pub fn get(&self, archive_path: &Path, size: u64, modified: u64) -> Option<&CacheEntry> {
+        self.entries
+            .get(&cache_key(archive_path))
+            .filter(|entry| entry.size == size && entry.modified == modified)
+    }
+
+    pub fn insert(&mut self, archive_path: &Path, entry: CacheEntry) {
+        self.entries.insert(cache_key(archive_path), entry);
+    }
+}
+
+fn cache_key(archive_path: &Path) -> String {
+    archive_path.to_string_lossy().to_string()
+}
+
+fn cache_file_path() -> io::Result<PathBuf> {
+    let dirs = ProjectDirs::from("com", "media-organizer-rust", "media-organizer").ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "Could not determine config directory")
+    })?;
+    let config_dir = dirs.config_dir();
+    fs::create_dir_all(config_dir)?;
+    Ok(config_dir.join(CACHE_FILE_NAME))
+}
+
+pub fn load_cache() -> io::Result<ArchiveCache> {
+    let path = cache_file_path()?;
+    if !path.exists() {
+        return Ok(ArchiveCache::default());
+    }
+    let data = fs::read_to_string(path)?;
+    serde_json::from_str(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+pub fn save_cache(cache: &ArchiveCache) -> io::Result<()> {
+    let path = cache_file_path()?;
+    let data = serde_json::to_string_pretty(cache)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    fs::write(path, data)
+}
+
+/// Size (bytes) and modified-time (seconds since the Unix epoch) for an archive on disk.
+pub fn archive_metadata(archive_path: &Path) -> io::Result<(u64, u64)> {
+    let metadata = fs::metadata(archive_path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?
+        .as_secs();
+    Ok((metadata.len(), modified))
+}
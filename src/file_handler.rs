@@ -1,21 +1,380 @@
-use image::{GenericImageView, ImageFormat, ImageReader};
+use bzip2::read::BzDecoder;
+use chardetng::EncodingDetector;
+use flate2::read::GzDecoder;
+use image::{DynamicImage, GenericImageView, ImageReader};
 use std::fs::{self, File};
-use std::io::{self, Read, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{Duration, SystemTime};
 use std::vec;
 use tar::Archive as TarArchive;
+use xz2::read::XzDecoder;
+use zip::read::ZipArchive;
 use zip::write::SimpleFileOptions;
 
 use crate::archive_cleaner;
 
 pub const IMAGE_SIZE: (u32, u32) = (1024, 1024);
 
+/// Compression wrapping a `.tar` container may be stored under.
+enum TarCompression {
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+}
+
+/// Detect the compression a tar stream is wrapped in, preferring magic bytes (which work
+/// regardless of extension) and falling back to the compound extension (`.tar.gz`/`.tgz`,
+/// `.tar.bz2`, `.tar.xz`) when none of the magic numbers match.
+fn detect_tar_compression(file_path: &Path, file: &mut File) -> io::Result<TarCompression> {
+    let mut magic = [0u8; 6];
+    let read = file.read(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    if read >= 2 && magic[0..2] == [0x1f, 0x8b] {
+        return Ok(TarCompression::Gzip);
+    }
+    if read >= 3 && magic[0..3] == [0x42, 0x5a, 0x68] {
+        return Ok(TarCompression::Bzip2);
+    }
+    if read >= 6 && magic[0..6] == [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00] {
+        return Ok(TarCompression::Xz);
+    }
+
+    let name = file_path.to_string_lossy().to_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Ok(TarCompression::Gzip)
+    } else if name.ends_with(".tar.bz2") {
+        Ok(TarCompression::Bzip2)
+    } else if name.ends_with(".tar.xz") {
+        Ok(TarCompression::Xz)
+    } else {
+        Ok(TarCompression::None)
+    }
+}
+
 pub struct FileHandler {
     dir_path: PathBuf,
     file_path: PathBuf,
     file_name: String,
+    options: FileHandlerOptions,
+}
+
+/// Video codec parameters `handle_video_file` passes to `ffmpeg`.
+#[derive(Debug, Clone)]
+pub struct VideoOptions {
+    pub video_codec: String,
+    pub audio_codec: String,
+    pub audio_language: String,
+    pub subtitle_language: String,
+}
+
+impl Default for VideoOptions {
+    fn default() -> Self {
+        Self {
+            video_codec: "libx264".to_string(),
+            audio_codec: "aac".to_string(),
+            audio_language: "jpn".to_string(),
+            subtitle_language: "eng".to_string(),
+        }
+    }
+}
+
+/// Output codec for the thumbnails `handle_image_file`/`handle_gif_file` produce.
+#[derive(Debug, Clone, Copy)]
+pub enum ImageCodec {
+    /// Lossless WebP, matching the original hardcoded `handle_image_file` behavior.
+    WebPLossless,
+    /// Lossy WebP at the configured quality.
+    WebP,
+    Avif,
+    Jxl,
+}
+
+impl ImageCodec {
+    fn extension(&self) -> &'static str {
+        match self {
+            ImageCodec::WebPLossless | ImageCodec::WebP => "webp",
+            ImageCodec::Avif => "avif",
+            ImageCodec::Jxl => "jxl",
+        }
+    }
+}
+
+/// Subtitle handling knobs. `handle_subtitle_file` always transcodes to UTF-8; these control
+/// the optional extra steps on top of that.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubtitleOptions {
+    pub convert_ass_to_srt: bool,
+    pub mux_into_video: bool,
+}
+
+/// Runtime-configurable knobs for [`FileHandler`], so one binary can target different
+/// size/quality budgets per run without recompiling. `Default` matches the handler's
+/// historical (hardcoded) behavior, so existing callers are unaffected.
+#[derive(Debug, Clone)]
+pub struct FileHandlerOptions {
+    pub thumbnail_size: (u32, u32),
+    pub image_codec: ImageCodec,
+    pub quality: u8,
+    pub video: VideoOptions,
+    pub subtitle: SubtitleOptions,
+}
+
+impl Default for FileHandlerOptions {
+    fn default() -> Self {
+        Self {
+            thumbnail_size: IMAGE_SIZE,
+            image_codec: ImageCodec::WebPLossless,
+            quality: 80,
+            video: VideoOptions::default(),
+            subtitle: SubtitleOptions::default(),
+        }
+    }
+}
+
+/// Encode `image` as `codec` at `quality` (ignored for `WebPLossless`), returning the encoded
+/// bytes.
+fn encode_thumbnail(image: &DynamicImage, codec: ImageCodec, quality: u8) -> Result<Vec<u8>, String> {
+    match codec {
+        ImageCodec::WebPLossless => {
+            let rgba_image = image.to_rgba8();
+            let mut data = Vec::new();
+            image::codecs::webp::WebPEncoder::new_lossless(&mut data)
+                .encode(
+                    rgba_image.as_raw(),
+                    rgba_image.width(),
+                    rgba_image.height(),
+                    image::ExtendedColorType::Rgba8,
+                )
+                .map_err(|e| format!("Failed to encode lossless WebP: {e}"))?;
+            Ok(data)
+        }
+        ImageCodec::WebP => {
+            let rgba_image = image.to_rgba8();
+            let encoder =
+                webp::Encoder::from_rgba(rgba_image.as_raw(), rgba_image.width(), rgba_image.height());
+            Ok(encoder.encode(quality as f32).to_vec())
+        }
+        ImageCodec::Avif => {
+            let mut data = Vec::new();
+            image
+                .write_with_encoder(image::codecs::avif::AvifEncoder::new_with_speed_quality(
+                    &mut data, 6, quality,
+                ))
+                .map_err(|e| format!("Failed to encode AVIF: {e}"))?;
+            Ok(data)
+        }
+        ImageCodec::Jxl => encode_jxl(image, quality),
+    }
+}
+
+#[cfg(feature = "jxl")]
+fn encode_jxl(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, String> {
+    use jpegxl_rs::encoder_builder;
+
+    let rgba_image = image.to_rgba8();
+    let mut encoder = encoder_builder()
+        .quality(quality as f32)
+        .build()
+        .map_err(|e| format!("Failed to build JPEG-XL encoder: {e}"))?;
+    encoder
+        .encode::<u8, u8>(rgba_image.as_raw(), rgba_image.width(), rgba_image.height())
+        .map(|result| result.data)
+        .map_err(|e| format!("Failed to encode JPEG-XL: {e}"))
+}
+
+#[cfg(not(feature = "jxl"))]
+fn encode_jxl(_image: &DynamicImage, _quality: u8) -> Result<Vec<u8>, String> {
+    Err("JPEG-XL support requires building with the `jxl` feature".to_string())
+}
+
+/// Detect a subtitle's encoding (commonly Shift-JIS or Windows-1252 for non-UTF-8 files) and
+/// decode it to a UTF-8 `String`.
+fn decode_to_utf8(raw: &[u8]) -> String {
+    let mut detector = EncodingDetector::new();
+    detector.feed(raw, true);
+    let (text, _encoding_used, _had_errors) = detector.guess(None, true).decode(raw);
+    text.into_owned()
+}
+
+/// Convert an ASS/SSA subtitle body to SRT: keep only `Dialogue:` lines, strip override tags
+/// (`{...}`), turn hard line breaks (`\N`/`\n`) into real newlines, and reformat the timing into SRT's
+/// `HH:MM:SS,mmm --> HH:MM:SS,mmm` form.
+fn ass_to_srt(ass_text: &str) -> String {
+    let mut srt = String::new();
+    let mut index = 1;
+
+    for line in ass_text.lines() {
+        let Some(rest) = line.strip_prefix("Dialogue:") else {
+            continue;
+        };
+        let fields: Vec<&str> = rest.splitn(10, ',').collect();
+        if fields.len() < 10 {
+            continue;
+        }
+
+        let start = ass_time_to_srt(fields[1].trim());
+        let end = ass_time_to_srt(fields[2].trim());
+        let text = strip_ass_overrides(fields[9]);
+
+        srt.push_str(&format!("{index}\n{start} --> {end}\n{text}\n\n"));
+        index += 1;
+    }
+
+    srt
+}
+
+/// Convert an ASS timestamp (`H:MM:SS.cc`, centiseconds) to an SRT timestamp
+/// (`HH:MM:SS,mmm`, milliseconds).
+fn ass_time_to_srt(time: &str) -> String {
+    let parts: Vec<&str> = time.split(':').collect();
+    let [hours, minutes, rest] = parts[..] else {
+        return "00:00:00,000".to_string();
+    };
+    let mut sec_parts = rest.split('.');
+    let seconds: u32 = sec_parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let centiseconds: u32 = sec_parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let hours: u32 = hours.parse().unwrap_or(0);
+    let minutes: u32 = minutes.parse().unwrap_or(0);
+    format!("{hours:02}:{minutes:02}:{seconds:02},{:03}", centiseconds * 10)
+}
+
+/// Strip ASS override blocks (`{\...}`) from a dialogue text field and turn its hard line
+/// breaks (`\N`/`\n`) into real newlines, leaving plain subtitle text.
+fn strip_ass_overrides(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_override = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => in_override = true,
+            '}' => in_override = false,
+            '\\' if !in_override && matches!(chars.peek(), Some('N') | Some('n')) => {
+                chars.next();
+                result.push('\n');
+            }
+            _ if !in_override => result.push(c),
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// A single entry observed while non-destructively walking an archive with [`FileHandler::list`].
+pub struct FileInArchive {
+    pub path: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+fn print_entry(entry: &FileInArchive) {
+    if entry.is_dir {
+        println!("{}/", entry.path);
+    } else {
+        println!("{} ({} bytes)", entry.path, entry.size);
+    }
+}
+
+/// The true category of a file, sniffed from its leading content bytes rather than trusted
+/// from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Zip,
+    Rar,
+    Tar,
+    Image,
+    Gif,
+    Video,
+    Subtitle,
+}
+
+impl MediaKind {
+    /// Whether `ext` is one of the extensions this kind is normally saved under. Used only to
+    /// flag content/extension mismatches; dispatch itself always trusts the sniffed kind.
+    fn matches_extension(&self, ext: &str) -> bool {
+        let ext = ext.to_lowercase();
+        match self {
+            MediaKind::Zip => ext == "zip",
+            MediaKind::Rar => ext == "rar",
+            MediaKind::Tar => matches!(ext.as_str(), "tar" | "gz" | "tgz" | "bz2" | "xz"),
+            MediaKind::Image => matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "bmp" | "webp"),
+            MediaKind::Gif => ext == "gif",
+            MediaKind::Video => matches!(ext.as_str(), "mp4" | "mkv"),
+            MediaKind::Subtitle => matches!(ext.as_str(), "srt" | "ass"),
+        }
+    }
+}
+
+/// Sniff `buf`'s leading bytes against a hand-rolled signature table, returning the first
+/// `MediaKind` whose magic matches.
+fn sniff_media_kind(buf: &[u8]) -> Option<MediaKind> {
+    if buf.len() >= 4 && &buf[0..4] == b"PK\x03\x04" {
+        return Some(MediaKind::Zip);
+    }
+    if buf.len() >= 7 && (&buf[0..7] == b"Rar!\x1a\x07\x00" || &buf[0..7] == b"Rar!\x1a\x07\x01") {
+        return Some(MediaKind::Rar);
+    }
+    if buf.len() >= 2 && buf[0..2] == [0x1f, 0x8b] {
+        return Some(MediaKind::Tar); // gzip magic; this codebase only stores gzip as .tar.gz
+    }
+    if buf.len() >= 3 && &buf[0..3] == b"BZh" {
+        return Some(MediaKind::Tar);
+    }
+    if buf.len() >= 6 && buf[0..6] == [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00] {
+        return Some(MediaKind::Tar);
+    }
+    if buf.len() >= 262 && &buf[257..262] == b"ustar" {
+        return Some(MediaKind::Tar);
+    }
+    if buf.len() >= 8 && &buf[0..8] == b"\x89PNG\r\n\x1a\n" {
+        return Some(MediaKind::Image);
+    }
+    if buf.len() >= 3 && buf[0..3] == [0xFF, 0xD8, 0xFF] {
+        return Some(MediaKind::Image);
+    }
+    if buf.len() >= 2 && &buf[0..2] == b"BM" {
+        return Some(MediaKind::Image);
+    }
+    if buf.len() >= 12 && &buf[0..4] == b"RIFF" && &buf[8..12] == b"WEBP" {
+        return Some(MediaKind::Image);
+    }
+    if buf.len() >= 6 && (&buf[0..6] == b"GIF87a" || &buf[0..6] == b"GIF89a") {
+        return Some(MediaKind::Gif);
+    }
+    if buf.len() >= 12 && &buf[4..8] == b"ftyp" {
+        return Some(MediaKind::Video);
+    }
+    if buf.len() >= 4 && buf[0..4] == [0x1A, 0x45, 0xDF, 0xA3] {
+        return Some(MediaKind::Video);
+    }
+    None
+}
+
+/// Normalize an archive entry's path for safe insertion into a zip: drop any root/prefix
+/// component and reject entries containing a `..` component (path traversal). Returns `None`
+/// for anything unsafe or that normalizes to nothing.
+pub(crate) fn sanitize_archive_entry_path(path: &Path) -> Option<String> {
+    use std::path::Component;
+
+    let mut sanitized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => return None,
+        }
+    }
+
+    if sanitized.as_os_str().is_empty() {
+        return None;
+    }
+
+    Some(sanitized.to_string_lossy().replace('\\', "/"))
 }
 
 pub fn extract_file_info<'a>(file_path: &'a Path) -> io::Result<(String, PathBuf)> {
@@ -48,31 +407,150 @@ pub fn extract_file_info<'a>(file_path: &'a Path) -> io::Result<(String, PathBuf
 }
 
 impl FileHandler {
-    pub fn new(archive_path: &Path) -> Self {
+    pub fn new(archive_path: &Path, options: FileHandlerOptions) -> Self {
         let (file_name, dir_path) = extract_file_info(archive_path).unwrap();
         Self {
             dir_path,
             file_path: archive_path.to_path_buf(),
             file_name,
+            options,
+        }
+    }
+
+    /// Sniff the file's true media category from its leading content bytes, using the
+    /// extension only as a fallback for text-based formats (subtitles) that have no magic.
+    pub fn detect_format(&self) -> Option<MediaKind> {
+        let mut file = File::open(&self.file_path).ok()?;
+        let mut buf = [0u8; 300];
+        let n = file.read(&mut buf).ok()?;
+
+        if let Some(kind) = sniff_media_kind(&buf[..n]) {
+            return Some(kind);
+        }
+
+        match self.file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("srt") | Some("ass") => Some(MediaKind::Subtitle),
+            _ => None,
         }
     }
 
     pub fn clean(&self) -> Result<(), String> {
+        let kind = self.detect_format().ok_or_else(|| {
+            format!(
+                "Could not determine file type: {}",
+                self.file_path.display()
+            )
+        })?;
+
+        if let Some(ext) = self.file_path.extension().and_then(|ext| ext.to_str()) {
+            if !kind.matches_extension(ext) {
+                eprintln!(
+                    "Warning: {} looks like {:?} content but has extension '.{}'; trusting the content",
+                    self.file_path.display(),
+                    kind,
+                    ext
+                );
+            }
+        }
+
+        match kind {
+            MediaKind::Zip => self.handle_zip_file(),
+            MediaKind::Rar => self.handle_rar_file(),
+            MediaKind::Tar => self.handle_tar_file(),
+            MediaKind::Image => self.handle_image_file(),
+            MediaKind::Gif => self.handle_gif_file(),
+            MediaKind::Subtitle => self.handle_subtitle_file(),
+            MediaKind::Video => self.handle_video_file(),
+        }
+    }
+
+    /// Walk the archive without extracting or modifying anything, printing each entry's path,
+    /// size, and directory flag as it is encountered.
+    pub fn list(&self) -> Result<(), String> {
         match self.file_path.extension().and_then(|ext| ext.to_str()) {
-            Some("zip") => self.handle_zip_file(),
-            Some("rar") => self.handle_rar_file(),
-            Some("tar") | Some("gz") => self.handle_tar_file(),
-            Some("jpg") | Some("jpeg") | Some("png") | Some("bmp") => self.handle_image_file(),
-            Some("gif") => self.handle_gif_file(),
-            Some("srt") | Some("ass") => self.handle_subtitle_file(),
-            Some("mp4") | Some("mkv") => self.handle_video_file(),
+            Some("zip") => self.list_zip_file(),
+            Some("rar") => self.list_rar_file(),
+            Some("tar") | Some("gz") | Some("tgz") | Some("bz2") | Some("xz") => {
+                self.list_tar_file()
+            }
             _ => Err(format!(
-                "Unsupported file format: {}",
+                "Unsupported archive format for listing: {}",
                 self.file_path.display()
             )),
         }
     }
 
+    fn list_zip_file(&self) -> Result<(), String> {
+        let zip_file =
+            File::open(&self.file_path).map_err(|e| format!("Failed to open zip file: {}", e))?;
+        let mut archive =
+            ZipArchive::new(zip_file).map_err(|e| format!("Failed to read zip archive: {}", e))?;
+
+        for i in 0..archive.len() {
+            let entry = archive
+                .by_index(i)
+                .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+            print_entry(&FileInArchive {
+                path: entry.name().to_string(),
+                size: entry.size(),
+                is_dir: entry.is_dir(),
+            });
+        }
+        Ok(())
+    }
+
+    fn list_tar_file(&self) -> Result<(), String> {
+        let mut tar_file = File::open(&self.file_path)
+            .map_err(|e| format!("Failed to open tar file: {}", e))?;
+        let compression = detect_tar_compression(&self.file_path, &mut tar_file)
+            .map_err(|e| format!("Failed to inspect tar file: {}", e))?;
+        let reader: Box<dyn Read> = match compression {
+            TarCompression::None => Box::new(tar_file),
+            TarCompression::Gzip => Box::new(GzDecoder::new(tar_file)),
+            TarCompression::Bzip2 => Box::new(BzDecoder::new(tar_file)),
+            TarCompression::Xz => Box::new(XzDecoder::new(tar_file)),
+        };
+        let mut archive = TarArchive::new(reader);
+
+        for entry in archive
+            .entries()
+            .map_err(|e| format!("Failed to read tar entries: {}", e))?
+        {
+            let entry = entry.map_err(|e| format!("Failed to read tar entry: {}", e))?;
+            let is_dir = entry.header().entry_type().is_dir();
+            let size = entry.header().size().unwrap_or(0);
+            let path = entry
+                .path()
+                .map_err(|e| format!("Failed to get entry path: {}", e))?
+                .to_string_lossy()
+                .to_string();
+            print_entry(&FileInArchive { path, size, is_dir });
+        }
+        Ok(())
+    }
+
+    fn list_rar_file(&self) -> Result<(), String> {
+        let mut archive = unrar::Archive::new(&self.file_path)
+            .open_for_processing()
+            .map_err(|e| format!("Failed to open RAR file: {}", e))?;
+
+        while let Some(header) = archive
+            .read_header()
+            .map_err(|e| format!("Failed to read RAR header: {}", e))?
+        {
+            let entry = header.entry();
+            print_entry(&FileInArchive {
+                path: entry.filename.to_string_lossy().to_string(),
+                size: entry.unpacked_size as u64,
+                is_dir: entry.is_directory(),
+            });
+            archive = header
+                .skip()
+                .map_err(|e| format!("Failed to skip RAR entry: {}", e))?;
+        }
+        Ok(())
+    }
+
     fn handle_zip_file(&self) -> Result<(), String> {
         match self.clean_archive_file() {
             Ok(_) => Ok(()),
@@ -97,7 +575,14 @@ impl FileHandler {
     }
 
     fn clean_archive_file(&self) -> Result<(), String> {
-        let archive_cleaner = archive_cleaner::ArchiveCleaner::new(&self.file_path);
+        let archive_cleaner = archive_cleaner::ArchiveCleaner::new(
+            &self.file_path,
+            false,
+            archive_cleaner::DEFAULT_DEDUPE_THRESHOLD,
+            None,
+            false,
+            archive_cleaner::OutputFormat::WebpLossless,
+        );
         match archive_cleaner.clean_archive_file(5) {
             Ok(_) => println!("Archive cleaned successfully."),
             Err(e) => println!("Failed to clean archive: {}", e),
@@ -106,10 +591,18 @@ impl FileHandler {
     }
 
     pub fn tar_to_zip(&self, zip_path: &Path) -> Result<(), String> {
-        // Open the TAR file
-        let tar_file =
+        // Open the TAR file, transparently decompressing it if it's gzip/bzip2/xz-wrapped
+        let mut tar_file =
             File::open(&self.file_path).map_err(|e| format!("Failed to open tar file: {}", e))?;
-        let mut archive = TarArchive::new(tar_file);
+        let compression = detect_tar_compression(&self.file_path, &mut tar_file)
+            .map_err(|e| format!("Failed to inspect tar file: {}", e))?;
+        let reader: Box<dyn Read> = match compression {
+            TarCompression::None => Box::new(tar_file),
+            TarCompression::Gzip => Box::new(GzDecoder::new(tar_file)),
+            TarCompression::Bzip2 => Box::new(BzDecoder::new(tar_file)),
+            TarCompression::Xz => Box::new(XzDecoder::new(tar_file)),
+        };
+        let mut archive = TarArchive::new(reader);
 
         // Create the ZIP file
         let zip_file =
@@ -122,15 +615,27 @@ impl FileHandler {
             .map_err(|e| format!("Failed to read tar entries: {}", e))?
         {
             let mut entry = entry.map_err(|e| format!("Failed to read tar entry: {}", e))?;
+            let is_dir = entry.header().entry_type().is_dir();
 
-            // Get the path of the TAR entry
+            // Get the path of the TAR entry, rejecting anything that would escape the
+            // archive root (e.g. `../../etc/foo`) once written into the zip
             let path = entry
                 .path()
                 .map_err(|e| format!("Failed to get entry path: {}", e))?;
-
-            // Start a new file in the ZIP archive
+            let Some(safe_name) = sanitize_archive_entry_path(&path) else {
+                continue;
+            };
+
+            if is_dir {
+                zip_writer
+                    .add_directory(format!("{safe_name}/"), SimpleFileOptions::default())
+                    .map_err(|e| format!("Failed to add zip directory entry: {}", e))?;
+                continue;
+            }
+
+            // Start a new file in the ZIP archive, preserving its relative folder hierarchy
             zip_writer
-                .start_file(path.to_string_lossy(), SimpleFileOptions::default())
+                .start_file(&safe_name, SimpleFileOptions::default())
                 .map_err(|e| format!("Failed to start zip file entry: {}", e))?;
 
             // Read the content of the tar entry and write it to the zip file
@@ -165,17 +670,41 @@ impl FileHandler {
         let mut zip_writer = zip::ZipWriter::new(zip_file);
 
         // Iterate over each entry in the RAR archive
-        while let Some(header) = archive.read_header().expect("read header") {
-            
+        while let Some(header) = archive
+            .read_header()
+            .map_err(|e| format!("Failed to read RAR header: {}", e))?
+        {
+            let entry = header.entry();
+            let is_dir = entry.is_directory();
+            let safe_name = sanitize_archive_entry_path(&entry.filename);
+
+            let Some(safe_name) = safe_name else {
+                archive = header
+                    .skip()
+                    .map_err(|e| format!("Failed to skip RAR entry: {}", e))?;
+                continue;
+            };
+
+            if is_dir {
+                zip_writer
+                    .add_directory(format!("{safe_name}/"), SimpleFileOptions::default())
+                    .map_err(|e| format!("Failed to add zip directory entry: {}", e))?;
+                archive = header
+                    .skip()
+                    .map_err(|e| format!("Failed to skip RAR entry: {}", e))?;
+                continue;
+            }
+
             zip_writer
-                .start_file(
-                    header.entry().filename.to_string_lossy().to_string(),
-                    SimpleFileOptions::default(),
-                )
+                .start_file(&safe_name, SimpleFileOptions::default())
                 .map_err(|e| format!("Failed to start zip file entry: {}", e))?;
 
-            let (data, cursor) = header.read().expect("read data");
-            zip_writer.write(&data).expect("write data");
+            let (data, cursor) = header
+                .read()
+                .map_err(|e| format!("Failed to read RAR entry data: {}", e))?;
+            zip_writer
+                .write_all(&data)
+                .map_err(|e| format!("Failed to write zip entry data: {}", e))?;
             archive = cursor;
         }
         zip_writer
@@ -191,30 +720,31 @@ impl FileHandler {
             .decode()
             .map_err(|e| format!("Failed to decode image: {}", e))?;
 
+        let target_ext = self.options.image_codec.extension();
         if self
             .file_path
             .extension()
-            .map_or(false, |ext| ext == "webp")
+            .map_or(false, |ext| ext == target_ext)
         {
             return Ok(());
         }
 
-        let image = image.thumbnail(IMAGE_SIZE.0, IMAGE_SIZE.1);
-        let webp_file_path = self.dir_path.join(format!("{}.webp", self.file_name));
+        let (width, height) = self.options.thumbnail_size;
+        let image = image.thumbnail(width, height);
+        let encoded = encode_thumbnail(&image, self.options.image_codec, self.options.quality)?;
+        let output_file_path = self.dir_path.join(format!("{}.{}", self.file_name, target_ext));
 
-        if !webp_file_path.exists() {
-            image
-                .save_with_format(webp_file_path, ImageFormat::WebP)
-                .map_err(|e| format!("Failed to save image as webp: {}", e))?;
+        if !output_file_path.exists() {
+            fs::write(&output_file_path, &encoded)
+                .map_err(|e| format!("Failed to save thumbnail: {}", e))?;
         } else {
             let timestamp = SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap_or(Duration::new(0, 0))
                 .as_secs();
-            let new_name = format!("{}_{timestamp}.webp", self.file_name);
-            image
-                .save_with_format(self.dir_path.join(new_name), ImageFormat::WebP)
-                .map_err(|e| format!("Failed to save image as webp: {}", e))?;
+            let new_name = format!("{}_{timestamp}.{}", self.file_name, target_ext);
+            fs::write(self.dir_path.join(new_name), &encoded)
+                .map_err(|e| format!("Failed to save thumbnail: {}", e))?;
         }
         fs::remove_file(&self.file_path)
             .map_err(|e| format!("Failed to remove old file: {}", e))?;
@@ -228,8 +758,9 @@ impl FileHandler {
             .map_err(|e| format!("Failed to decode gif file: {}", e))?;
 
         let (width, height) = gif_image.dimensions();
-        if width > IMAGE_SIZE.0 && height > IMAGE_SIZE.1 {
-            let resized_image = gif_image.thumbnail(IMAGE_SIZE.0, IMAGE_SIZE.1);
+        let (target_width, target_height) = self.options.thumbnail_size;
+        if width > target_width && height > target_height {
+            let resized_image = gif_image.thumbnail(target_width, target_height);
             resized_image
                 .save(self.dir_path.join(format!("{}.gif", self.file_name)))
                 .map_err(|e| format!("Failed to save gif file: {}", e))?;
@@ -238,19 +769,20 @@ impl FileHandler {
     }
 
     fn handle_video_file(&self) -> Result<(), String> {
+        let video = &self.options.video;
         let command = Command::new("ffmpeg")
             .arg("-i")
             .arg(&self.file_path)
             .arg("-c:v")
-            .arg("libx264")
+            .arg(&video.video_codec)
             .arg("-c:a")
-            .arg("aac")
+            .arg(&video.audio_codec)
             .arg("-c:s")
             .arg("mov_text")
             .arg("-metadata:s:a:0")
-            .arg("language=jpn")
+            .arg(format!("language={}", video.audio_language))
             .arg("-metadata:s:s:0")
-            .arg("language=eng")
+            .arg(format!("language={}", video.subtitle_language))
             .arg(self.dir_path.join(format!("{}.mp4", self.file_name)))
             .output()
             .map_err(|e| format!("Failed to execute ffmpeg command: {}", e))?;
@@ -266,15 +798,135 @@ impl FileHandler {
         Ok(())
     }
 
+    /// Re-encode the subtitle to UTF-8 (source files commonly ship as Shift-JIS or
+    /// Windows-1252), optionally convert `.ass` to `.srt`, and optionally mux the result into
+    /// a matching sibling video.
     fn handle_subtitle_file(&self) -> Result<(), String> {
-        // Subtitle files handling logic if necessary.
+        let raw = fs::read(&self.file_path)
+            .map_err(|e| format!("Failed to read subtitle file: {}", e))?;
+        let utf8_text = decode_to_utf8(&raw);
+
+        let is_ass = self
+            .file_path
+            .extension()
+            .map_or(false, |ext| ext == "ass");
+        let (output_text, output_ext) = if is_ass && self.options.subtitle.convert_ass_to_srt {
+            (ass_to_srt(&utf8_text), "srt")
+        } else {
+            (utf8_text, if is_ass { "ass" } else { "srt" })
+        };
+
+        let output_path = self.dir_path.join(format!("{}.{}", self.file_name, output_ext));
+        fs::write(&output_path, &output_text)
+            .map_err(|e| format!("Failed to write subtitle file: {}", e))?;
+        if output_path != self.file_path {
+            fs::remove_file(&self.file_path)
+                .map_err(|e| format!("Failed to remove old subtitle file: {}", e))?;
+        }
+
+        if self.options.subtitle.mux_into_video {
+            if let Some(video_path) = self.matching_video_path() {
+                self.mux_subtitle_into(&video_path, &output_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The sibling video this subtitle would be muxed into, if one with a matching stem exists
+    /// alongside it.
+    fn matching_video_path(&self) -> Option<PathBuf> {
+        ["mp4", "mkv"]
+            .iter()
+            .map(|ext| self.dir_path.join(format!("{}.{}", self.file_name, ext)))
+            .find(|candidate| candidate.exists())
+    }
+
+    /// Mux `subtitle_path` into `video_path` as a `mov_text` track tagged with
+    /// `subtitle_language`, the same convention `handle_video_file` uses for its own subtitle
+    /// track, so both handlers agree on how a subtitle ends up in the final file.
+    pub fn mux_subtitle_into(&self, video_path: &Path, subtitle_path: &Path) -> Result<(), String> {
+        let muxed_path = video_path.with_extension("muxed.mp4");
+        let command = Command::new("ffmpeg")
+            .arg("-i")
+            .arg(video_path)
+            .arg("-i")
+            .arg(subtitle_path)
+            .arg("-c")
+            .arg("copy")
+            .arg("-c:s")
+            .arg("mov_text")
+            .arg("-metadata:s:s:0")
+            .arg(format!("language={}", self.options.video.subtitle_language))
+            .arg(&muxed_path)
+            .output()
+            .map_err(|e| format!("Failed to execute ffmpeg command: {}", e))?;
+
+        if !command.status.success() {
+            return Err(format!(
+                "FFmpeg mux failed: {}",
+                String::from_utf8_lossy(&command.stderr)
+            ));
+        }
+        fs::rename(&muxed_path, video_path)
+            .map_err(|e| format!("Failed to replace video with muxed version: {}", e))?;
         Ok(())
     }
 
     pub fn get_supported_extensions() -> Vec<&'static str> {
         vec![
-            "zip", "rar", "tar", "gz", "jpg", "jpeg", "png", "bmp", "gif", "webp", "mp4", "mkv",
-            "srt", "ass",
+            "zip", "rar", "tar", "gz", "tgz", "bz2", "xz", "jpg", "jpeg", "png", "bmp", "gif",
+            "webp", "mp4", "mkv", "srt", "ass",
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a gzip-compressed tar containing `entries` (name, content) pairs.
+    fn build_gzip_tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        let tar_bytes = builder.into_inner().unwrap();
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar_bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn tar_to_zip_writes_each_entry_into_output_zip() {
+        let gz_bytes = build_gzip_tar(&[("a.txt", b"hello"), ("dir/b.txt", b"world")]);
+
+        let temp_dir =
+            std::env::temp_dir().join(format!("media-organizer-tar-to-zip-test-{}", std::process::id()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let tar_path = temp_dir.join("fixture.tar.gz");
+        fs::write(&tar_path, &gz_bytes).unwrap();
+        let zip_path = temp_dir.join("fixture.zip");
+
+        let handler = FileHandler::new(&tar_path, FileHandlerOptions::default());
+        handler.tar_to_zip(&zip_path).expect("tar_to_zip should succeed");
+
+        let zip_file = File::open(&zip_path).unwrap();
+        let mut zip = ZipArchive::new(zip_file).unwrap();
+
+        let mut a_data = Vec::new();
+        zip.by_name("a.txt").unwrap().read_to_end(&mut a_data).unwrap();
+        assert_eq!(a_data, b"hello");
+
+        let mut b_data = Vec::new();
+        zip.by_name("dir/b.txt").unwrap().read_to_end(&mut b_data).unwrap();
+        assert_eq!(b_data, b"world");
+
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+}
@@ -1,4 +1,5 @@
 use chrono::Utc;
+use crossbeam_channel::Sender;
 use regex::Regex;
 use std::ffi::OsString;
 use std::fs;
@@ -7,6 +8,8 @@ use std::path::Path;
 use std::thread::sleep;
 use std::time::Duration;
 
+use crate::progress::{self, ProgressData, StopFlag};
+
 /// Slugifies a given string: removes non-alphanumeric characters, trims, replaces spaces with hyphens, and normalizes hyphens.
 fn custom_slugify(input: &str) -> String {
     let re = Regex::new(r"[^a-zA-Z0-9\s]").unwrap();
@@ -70,34 +73,53 @@ fn sanitize_filename(mut name: String) -> String {
 
 /// Cleans all entries in the given directory path.
 pub fn clean(content_path: &Path) -> io::Result<()> {
+    clean_with_progress(content_path, None, None)
+}
+
+/// Same as [`clean`], but reports each step via `progress_tx` instead of printing it, and
+/// checks `stop` between entries so a caller can cancel mid-run.
+pub fn clean_with_progress(
+    content_path: &Path,
+    stop: Option<&StopFlag>,
+    progress_tx: Option<&Sender<ProgressData>>,
+) -> io::Result<()> {
+    let total = content_path.read_dir()?.count();
+
     for (processed, entry) in fs::read_dir(content_path)?
         .filter_map(Result::ok)
         .enumerate()
     {
+        if stop.is_some_and(progress::is_stopped) {
+            break;
+        }
+
         let path = entry.path();
-        println!(
-            "Progress: {}/{} ({}%) - Processing: {}",
-            processed + 1,
-            content_path.read_dir()?.count(),
-            (processed + 1) * 100 / content_path.read_dir()?.count(),
-            path.display()
-        );
+        if let Some(tx) = progress_tx {
+            let _ = tx.send(ProgressData {
+                current_stage: 1,
+                max_stage: 1,
+                items_checked: processed + 1,
+                items_to_check: total,
+            });
+        }
 
         if path.is_dir() {
-            handle_directory(&path)?;
+            handle_directory(&path, stop, progress_tx)?;
         } else {
             eprintln!("Error: Not a directory - {}", path.display());
         }
     }
-    println!("Cleaning complete.");
     Ok(())
 }
 
 /// Handles directory cleaning by checking contents, renaming, and recursively cleaning.
-fn handle_directory(path: &Path) -> io::Result<()> {
+fn handle_directory(
+    path: &Path,
+    stop: Option<&StopFlag>,
+    progress_tx: Option<&Sender<ProgressData>>,
+) -> io::Result<()> {
     if fs::read_dir(path)?.next().is_none() {
         fs::remove_dir(path)?;
-        println!("Removed empty folder: {}", path.display());
     } else {
         let old_name = path
             .file_name()
@@ -109,10 +131,9 @@ fn handle_directory(path: &Path) -> io::Result<()> {
         if new_name != old_name {
             let new_path = path.with_file_name(new_name);
             fs::rename(&path, &new_path)?;
-            println!("Renamed folder: {} -> {}", old_name, new_path.display());
-            clean(&new_path)?;
+            clean_with_progress(&new_path, stop, progress_tx)?;
         } else {
-            clean(path)?;
+            clean_with_progress(path, stop, progress_tx)?;
         }
     }
     Ok(())
@@ -137,14 +158,3 @@ fn sep_author_name(name: &str) -> (Option<String>, String) {
     let cleaned_author = author.map(sanitize_filename);
     (cleaned_author, cleaned_item_name)
 }
-
-pub fn main() {
-    let example_name = "  [Special](example) {test} title chapter 01 20231128 123456  ".to_string();
-    let cleaned_name = sanitize_filename(example_name);
-    println!("Cleaned name: {}", cleaned_name);
-
-    // Adjust path accordingly
-    if let Err(e) = clean(Path::new("path/to/content")) {
-        eprintln!("Error during cleaning: {}", e);
-    }
-}
@@ -0,0 +1,122 @@
+//! Decoding for page formats beyond what the `image` crate handles out of the box: HEIF/AVIF
+//! (camera/Apple formats), common RAW formats, and JPEG-XL. Each is behind its own Cargo
+//! feature so a build can opt into only the codecs it needs; everything else still falls back
+//! to `image::load_from_memory`.
+
+use image::DynamicImage;
+use std::io;
+use std::path::Path;
+
+/// Extensions (lowercase, without the leading dot) that this module knows how to route to a
+/// decoder, on top of whatever `image::load_from_memory` already supports.
+const HEIF_EXTENSIONS: &[&str] = &["heic", "heif", "avif"];
+const RAW_EXTENSIONS: &[&str] = &["dng", "cr2", "nef", "arw"];
+const JXL_EXTENSIONS: &[&str] = &["jxl"];
+
+/// Whether `name` (a zip entry name or file path) has an extension this module can decode,
+/// either natively via `image` or through one of the feature-gated paths below.
+pub fn is_image(name: &str) -> bool {
+    match extension_of(name) {
+        Some(ext) => {
+            matches!(ext.as_str(), "webp" | "jpg" | "jpeg" | "png")
+                || HEIF_EXTENSIONS.contains(&ext.as_str())
+                || RAW_EXTENSIONS.contains(&ext.as_str())
+                || JXL_EXTENSIONS.contains(&ext.as_str())
+        }
+        None => false,
+    }
+}
+
+/// Decode a page's raw bytes into a [`DynamicImage`], routing by file extension to whichever
+/// decoder understands the format.
+pub fn decode_page(name: &str, data: &[u8]) -> io::Result<DynamicImage> {
+    match extension_of(name).as_deref() {
+        Some(ext) if HEIF_EXTENSIONS.contains(&ext) => decode_heif(data),
+        Some(ext) if RAW_EXTENSIONS.contains(&ext) => decode_raw(data),
+        Some(ext) if JXL_EXTENSIONS.contains(&ext) => decode_jxl(data),
+        _ => image::load_from_memory(data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+    }
+}
+
+fn extension_of(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+#[cfg(feature = "heif")]
+fn decode_heif(data: &[u8]) -> io::Result<DynamicImage> {
+    use libheif_rs::{ColorSpace, HeifContext, RgbChroma};
+
+    let ctx = HeifContext::read_from_bytes(data)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("HEIF/AVIF: {e}")))?;
+    let handle = ctx
+        .primary_image_handle()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("HEIF/AVIF: {e}")))?;
+    let image = handle
+        .decode(ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("HEIF/AVIF: {e}")))?;
+
+    let width = image.width();
+    let height = image.height();
+    let plane = image.planes().interleaved.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "HEIF/AVIF: no interleaved plane")
+    })?;
+
+    let buffer = image::RgbaImage::from_raw(width, height, plane.data.to_vec())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "HEIF/AVIF: buffer size mismatch"))?;
+    Ok(DynamicImage::ImageRgba8(buffer))
+}
+
+#[cfg(not(feature = "heif"))]
+fn decode_heif(_data: &[u8]) -> io::Result<DynamicImage> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "HEIF/AVIF support requires building with the `heif` feature",
+    ))
+}
+
+#[cfg(feature = "raw")]
+fn decode_raw(data: &[u8]) -> io::Result<DynamicImage> {
+    use std::io::Cursor;
+
+    let raw_image = rawloader::decode(&mut Cursor::new(data))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("RAW: {e:?}")))?;
+    let decoded = imagepipe::Pipeline::new_from_source(imagepipe::ImageSource::Raw(raw_image))
+        .and_then(|mut pipeline| pipeline.output_8bit(None))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("RAW: {e:?}")))?;
+
+    let buffer = image::RgbImage::from_raw(decoded.width as u32, decoded.height as u32, decoded.data)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "RAW: buffer size mismatch"))?;
+    Ok(DynamicImage::ImageRgb8(buffer))
+}
+
+#[cfg(not(feature = "raw"))]
+fn decode_raw(_data: &[u8]) -> io::Result<DynamicImage> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "RAW support requires building with the `raw` feature",
+    ))
+}
+
+#[cfg(feature = "jxl")]
+fn decode_jxl(data: &[u8]) -> io::Result<DynamicImage> {
+    let image = jxl_oxide::JxlImage::from_reader(data)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("JPEG-XL: {e}")))?
+        .render_frame(0)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("JPEG-XL: {e}")))?
+        .image();
+
+    let buffer = image::RgbaImage::from_raw(image.width() as u32, image.height() as u32, image.into_raw())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "JPEG-XL: buffer size mismatch"))?;
+    Ok(DynamicImage::ImageRgba8(buffer))
+}
+
+#[cfg(not(feature = "jxl"))]
+fn decode_jxl(_data: &[u8]) -> io::Result<DynamicImage> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "JPEG-XL support requires building with the `jxl` feature",
+    ))
+}
@@ -1,22 +1,315 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 pub mod archive_cleaner;
+pub mod cache;
 pub mod file_handler;
 pub mod formatter;
+pub mod image_decode;
+pub mod progress;
+pub mod self_update;
+
+use archive_cleaner::{ArchiveCleaner, OutputFormat, DEFAULT_DEDUPE_THRESHOLD};
+use file_handler::{FileHandler, FileHandlerOptions, ImageCodec, VideoOptions};
+
+/// Output codec choice exposed on the CLI; `quality` is ignored for lossless WebP.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormatArg {
+    #[value(name = "webp-lossless")]
+    WebpLossless,
+    #[value(name = "webp-lossy")]
+    WebpLossy,
+    #[value(name = "avif")]
+    Avif,
+}
+
+impl OutputFormatArg {
+    fn resolve(self, quality: u8) -> OutputFormat {
+        match self {
+            OutputFormatArg::WebpLossless => OutputFormat::WebpLossless,
+            OutputFormatArg::WebpLossy => OutputFormat::WebpLossy(quality),
+            OutputFormatArg::Avif => OutputFormat::Avif(quality),
+        }
+    }
+}
+
+/// Thumbnail codec choice exposed on the CLI for [`Commands::CleanFile`].
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ImageCodecArg {
+    #[value(name = "webp")]
+    WebP,
+    #[value(name = "avif")]
+    Avif,
+    #[value(name = "jxl")]
+    Jxl,
+}
+
+impl From<ImageCodecArg> for ImageCodec {
+    fn from(codec: ImageCodecArg) -> Self {
+        match codec {
+            ImageCodecArg::WebP => ImageCodec::WebP,
+            ImageCodecArg::Avif => ImageCodec::Avif,
+            ImageCodecArg::Jxl => ImageCodec::Jxl,
+        }
+    }
+}
 
-// #[derive(Args, Debug)]
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
-struct Args {
-    #[arg(short, long)]
-    module: String,
+struct Cli {
+    /// Check GitHub releases for a newer build and replace this binary in place.
+    #[arg(long)]
+    self_update: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Sanitize and flatten folder names under a content directory.
+    Format {
+        path: PathBuf,
+    },
+    /// Clean a single manga/manhwa archive: resize, re-encode, and optionally dedupe its pages.
+    CleanArchive {
+        file: PathBuf,
+        /// How many leading pages to sample when deciding whether the archive needs rewriting.
+        #[arg(long, default_value_t = 5)]
+        max_check: usize,
+        #[arg(long)]
+        dedupe: bool,
+        #[arg(long, default_value_t = DEFAULT_DEDUPE_THRESHOLD)]
+        dedupe_threshold: u32,
+        /// Worker thread count for the encode pool; defaults to available parallelism.
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Bypass the on-disk cache and reprocess even if this archive was already cleaned.
+        #[arg(long)]
+        force: bool,
+        #[arg(long, value_enum, default_value = "webp-lossless")]
+        format: OutputFormatArg,
+        /// Quality 0-100; ignored for `webp-lossless`.
+        #[arg(long, default_value_t = 80)]
+        quality: u8,
+    },
+    /// Remove near-duplicate pages from an archive, copying every surviving page out
+    /// byte-for-byte (no resize or re-encode), unlike `clean-archive --dedupe`.
+    Dedupe {
+        file: PathBuf,
+        #[arg(long, default_value_t = DEFAULT_DEDUPE_THRESHOLD)]
+        threshold: u32,
+    },
+    /// Report unreadable/corrupt pages in an archive without modifying it.
+    ScanBroken {
+        file: PathBuf,
+    },
+    /// Preview a zip/rar/tar archive's contents without extracting or modifying it.
+    List {
+        file: PathBuf,
+    },
+    /// Sniff a single file's content and clean it per its kind (archive/image/gif/video/subtitle),
+    /// with the target thumbnail size, image codec, and video codec params configurable per run.
+    CleanFile {
+        file: PathBuf,
+        /// Target thumbnail width for images and GIFs.
+        #[arg(long, default_value_t = file_handler::IMAGE_SIZE.0)]
+        thumbnail_width: u32,
+        /// Target thumbnail height for images and GIFs.
+        #[arg(long, default_value_t = file_handler::IMAGE_SIZE.1)]
+        thumbnail_height: u32,
+        #[arg(long, value_enum, default_value = "webp")]
+        image_codec: ImageCodecArg,
+        /// Quality 0-100; ignored for lossless codecs.
+        #[arg(long, default_value_t = 80)]
+        quality: u8,
+        #[arg(long, default_value = "libx264")]
+        video_codec: String,
+        #[arg(long, default_value = "aac")]
+        audio_codec: String,
+        #[arg(long, default_value = "jpn")]
+        audio_language: String,
+        #[arg(long, default_value = "eng")]
+        subtitle_language: String,
+        /// Convert `.ass` subtitles to `.srt` instead of keeping them as `.ass`.
+        #[arg(long)]
+        convert_ass_to_srt: bool,
+        /// Mux a cleaned sidecar subtitle into its matching video via ffmpeg.
+        #[arg(long)]
+        mux_subtitle: bool,
+    },
+    /// Walk a directory and apply archive cleaning to every .cbz/.zip file found in it.
+    Process {
+        dir: PathBuf,
+        #[arg(long, default_value_t = 5)]
+        max_check: usize,
+        #[arg(long)]
+        threads: Option<usize>,
+        #[arg(long)]
+        force: bool,
+        #[arg(long, value_enum, default_value = "webp-lossless")]
+        format: OutputFormatArg,
+        #[arg(long, default_value_t = 80)]
+        quality: u8,
+    },
 }
 
 fn main() {
-    let args = Args::parse();
-    match args.module.as_str() {
-        "formatter" => formatter::main(),
-        _ => println!("Unknown module: {}", args.module),
-        
+    let cli = Cli::parse();
+
+    if cli.self_update {
+        match self_update::SelfUpdate::run() {
+            Ok(Some(update)) => {
+                println!("Updated from v{} to v{}", update.old_version, update.new_version)
+            }
+            Ok(None) => println!("Already up to date."),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
     }
+
+    let Some(command) = cli.command else {
+        eprintln!("Error: no subcommand given (use --help to see available subcommands)");
+        std::process::exit(1);
+    };
+
+    let result = match command {
+        Commands::Format { path } => formatter::clean(&path).map_err(|e| e.to_string()),
+        Commands::CleanArchive {
+            file,
+            max_check,
+            dedupe,
+            dedupe_threshold,
+            threads,
+            force,
+            format,
+            quality,
+        } => ArchiveCleaner::new(
+            &file,
+            dedupe,
+            dedupe_threshold,
+            threads,
+            force,
+            format.resolve(quality),
+        )
+        .clean_archive_file(max_check)
+        .map_err(|e| e.to_string()),
+        Commands::Dedupe { file, threshold } => {
+            ArchiveCleaner::new(&file, true, threshold, None, true, OutputFormat::WebpLossless)
+                .dedupe_archive_file()
+                .map_err(|e| e.to_string())
+        }
+        Commands::ScanBroken { file } => ArchiveCleaner::new(
+            &file,
+            false,
+            DEFAULT_DEDUPE_THRESHOLD,
+            None,
+            false,
+            OutputFormat::WebpLossless,
+        )
+        .scan_broken()
+        .map(report_broken_entries)
+        .map_err(|e| e.to_string()),
+        Commands::List { file } => FileHandler::new(&file, FileHandlerOptions::default()).list(),
+        Commands::CleanFile {
+            file,
+            thumbnail_width,
+            thumbnail_height,
+            image_codec,
+            quality,
+            video_codec,
+            audio_codec,
+            audio_language,
+            subtitle_language,
+            convert_ass_to_srt,
+            mux_subtitle,
+        } => {
+            let options = FileHandlerOptions {
+                thumbnail_size: (thumbnail_width, thumbnail_height),
+                image_codec: image_codec.into(),
+                quality,
+                video: VideoOptions {
+                    video_codec,
+                    audio_codec,
+                    audio_language,
+                    subtitle_language,
+                },
+                subtitle: file_handler::SubtitleOptions {
+                    convert_ass_to_srt,
+                    mux_into_video: mux_subtitle,
+                },
+            };
+            FileHandler::new(&file, options).clean()
+        }
+        Commands::Process {
+            dir,
+            max_check,
+            threads,
+            force,
+            format,
+            quality,
+        } => process_directory(&dir, max_check, threads, force, format.resolve(quality)),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn report_broken_entries(broken: Vec<archive_cleaner::BrokenEntry>) {
+    if broken.is_empty() {
+        println!("No broken pages found.");
+        return;
+    }
+    for entry in &broken {
+        println!(
+            "{}: {} ({} bytes) - {}",
+            entry.path.display(),
+            entry.inner_name,
+            entry.size,
+            entry.error_string
+        );
+    }
+}
+
+fn process_directory(
+    dir: &Path,
+    max_check: usize,
+    threads: Option<usize>,
+    force: bool,
+    output_format: OutputFormat,
+) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {e}", dir.display()))?;
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let is_archive = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("cbz") || ext.eq_ignore_ascii_case("zip"));
+        if !is_archive {
+            continue;
+        }
+
+        println!("Processing {}", path.display());
+        let cleaner = ArchiveCleaner::new(
+            &path,
+            false,
+            DEFAULT_DEDUPE_THRESHOLD,
+            threads,
+            force,
+            output_format,
+        );
+        if let Err(e) = cleaner.clean_archive_file(max_check) {
+            eprintln!("Failed to clean {}: {e}", path.display());
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,26 @@
+//! Progress reporting and cooperative cancellation shared by the cleaning routines, so callers
+//! (a GUI, a server job runner, ...) can observe progress and abort mid-run instead of relying
+//! on stdout and an uninterruptible loop.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A progress update emitted by a long-running cleaning routine.
+#[derive(Debug, Clone)]
+pub struct ProgressData {
+    pub current_stage: usize,
+    pub max_stage: usize,
+    pub items_checked: usize,
+    pub items_to_check: usize,
+}
+
+/// Shared flag a caller can set to ask an in-progress run to stop at the next checkpoint.
+pub type StopFlag = Arc<AtomicBool>;
+
+pub fn new_stop_flag() -> StopFlag {
+    Arc::new(AtomicBool::new(false))
+}
+
+pub fn is_stopped(stop: &StopFlag) -> bool {
+    stop.load(Ordering::Relaxed)
+}
@@ -0,0 +1,227 @@
+//! Self-update: check the configured GitHub releases repo for a newer version, download the
+//! release asset matching this platform, extract it, and replace the running executable.
+
+use serde::Deserialize;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zip::read::ZipArchive;
+
+const GITHUB_REPO: &str = "Tee55/media-organizer-rust";
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Outcome of a successful [`SelfUpdate::run`].
+pub struct SelfUpdate {
+    pub old_version: String,
+    pub new_version: String,
+}
+
+impl SelfUpdate {
+    /// Check GitHub for a newer release, download and extract the asset matching this
+    /// platform, and replace the running executable. Returns `Ok(None)` if this
+    /// build is already the latest release.
+    pub fn run() -> Result<Option<SelfUpdate>, String> {
+        let release = fetch_latest_release()?;
+        let new_version = release.tag_name.trim_start_matches('v').to_string();
+        if new_version == CURRENT_VERSION {
+            return Ok(None);
+        }
+
+        let asset = select_asset(&release.assets).ok_or_else(|| {
+            format!("No release asset found for this platform ({})", platform_tag())
+        })?;
+        let archive_bytes = download(&asset.browser_download_url)?;
+
+        let extract_dir = env::temp_dir().join(format!("media-organizer-update-{new_version}"));
+        fs::create_dir_all(&extract_dir)
+            .map_err(|e| format!("Failed to create temp dir: {e}"))?;
+
+        let binary_path = if asset.name.ends_with(".zip") {
+            extract_zip(&archive_bytes, &extract_dir)?
+        } else {
+            extract_tar_gz(&archive_bytes, &extract_dir)?
+        };
+
+        replace_current_exe(&binary_path)?;
+        let _ = fs::remove_dir_all(&extract_dir);
+
+        Ok(Some(SelfUpdate {
+            old_version: CURRENT_VERSION.to_string(),
+            new_version,
+        }))
+    }
+}
+
+fn platform_tag() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "windows"
+    } else if cfg!(target_os = "macos") {
+        "macos"
+    } else {
+        "linux"
+    }
+}
+
+fn select_asset(assets: &[Asset]) -> Option<&Asset> {
+    let tag = platform_tag();
+    assets.iter().find(|asset| asset.name.to_lowercase().contains(tag))
+}
+
+fn fetch_latest_release() -> Result<Release, String> {
+    let url = format!("https://api.github.com/repos/{GITHUB_REPO}/releases/latest");
+    reqwest::blocking::Client::new()
+        .get(&url)
+        .header("User-Agent", "media-organizer-rust-self-update")
+        .send()
+        .map_err(|e| format!("Failed to reach GitHub: {e}"))?
+        .json::<Release>()
+        .map_err(|e| format!("Failed to parse GitHub release response: {e}"))
+}
+
+fn download(url: &str) -> Result<Vec<u8>, String> {
+    reqwest::blocking::Client::new()
+        .get(url)
+        .header("User-Agent", "media-organizer-rust-self-update")
+        .send()
+        .map_err(|e| format!("Failed to download release asset: {e}"))?
+        .bytes()
+        .map(|b| b.to_vec())
+        .map_err(|e| format!("Failed to read release asset: {e}"))
+}
+
+/// Extract `data` (a zip archive) into `dest_dir`, creating parent directories for each entry
+/// before `File::create` so a binary nested under a subfolder (e.g.
+/// `media-organizer/bin/media-organizer`) doesn't fail with "No such file or directory".
+/// Returns the path of the extracted executable.
+fn extract_zip(data: &[u8], dest_dir: &Path) -> Result<PathBuf, String> {
+    let mut archive = ZipArchive::new(io::Cursor::new(data))
+        .map_err(|e| format!("Failed to read update archive: {e}"))?;
+    let mut binary_path = None;
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read update archive entry: {e}"))?;
+        let Some(entry_path) = entry.enclosed_name() else {
+            continue;
+        };
+        let out_path = dest_dir.join(&entry_path);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path)
+                .map_err(|e| format!("Failed to create directory: {e}"))?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+
+        let mut out_file = File::create(&out_path)
+            .map_err(|e| format!("Failed to create {}: {e}", out_path.display()))?;
+        io::copy(&mut entry, &mut out_file)
+            .map_err(|e| format!("Failed to extract {}: {e}", out_path.display()))?;
+
+        if is_executable_name(&out_path) {
+            binary_path = Some(out_path);
+        }
+    }
+
+    binary_path.ok_or_else(|| "Update archive did not contain the executable".to_string())
+}
+
+/// Extract `data` (a gzip-compressed tar) into `dest_dir`, applying the same
+/// create-parent-directories-first treatment as [`extract_zip`]. Entry paths are sanitized with
+/// [`crate::file_handler::sanitize_archive_entry_path`] the same way the cleaner's rar/tar to
+/// zip conversion does, so an absolute or `../`-escaping entry in the downloaded release asset
+/// can't write outside `dest_dir`.
+fn extract_tar_gz(data: &[u8], dest_dir: &Path) -> Result<PathBuf, String> {
+    let decoder = flate2::read::GzDecoder::new(io::Cursor::new(data));
+    let mut archive = tar::Archive::new(decoder);
+    let mut binary_path = None;
+
+    for entry in archive
+        .entries()
+        .map_err(|e| format!("Failed to read update archive: {e}"))?
+    {
+        let mut entry = entry.map_err(|e| format!("Failed to read update archive entry: {e}"))?;
+        let entry_path = entry
+            .path()
+            .map_err(|e| format!("Failed to get entry path: {e}"))?
+            .to_path_buf();
+        let Some(safe_name) = crate::file_handler::sanitize_archive_entry_path(&entry_path) else {
+            continue;
+        };
+        let out_path = dest_dir.join(safe_name);
+
+        if entry.header().entry_type().is_dir() {
+            fs::create_dir_all(&out_path)
+                .map_err(|e| format!("Failed to create directory: {e}"))?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {e}"))?;
+        }
+
+        let mut out_file = File::create(&out_path)
+            .map_err(|e| format!("Failed to create {}: {e}", out_path.display()))?;
+        io::copy(&mut entry, &mut out_file)
+            .map_err(|e| format!("Failed to extract {}: {e}", out_path.display()))?;
+
+        if is_executable_name(&out_path) {
+            binary_path = Some(out_path);
+        }
+    }
+
+    binary_path.ok_or_else(|| "Update archive did not contain the executable".to_string())
+}
+
+fn is_executable_name(path: &Path) -> bool {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().contains("media-organizer"))
+        .unwrap_or(false)
+}
+
+/// Replace the currently running executable with `new_binary`: the old binary is renamed
+/// aside first and renamed back if copying the new one fails. The swap is not atomic: if the
+/// process dies after that rename and before the copy completes, no complete executable is
+/// left at the original path.
+fn replace_current_exe(new_binary: &Path) -> Result<(), String> {
+    let current_exe =
+        env::current_exe().map_err(|e| format!("Failed to locate running executable: {e}"))?;
+    let staged = current_exe.with_extension("old");
+    fs::rename(&current_exe, &staged).map_err(|e| format!("Failed to stage old executable: {e}"))?;
+
+    if let Err(e) = fs::copy(new_binary, &current_exe) {
+        let _ = fs::rename(&staged, &current_exe);
+        return Err(format!("Failed to install new executable: {e}"));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&current_exe)
+            .map_err(|e| format!("Failed to read new executable permissions: {e}"))?
+            .permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&current_exe, perms)
+            .map_err(|e| format!("Failed to set new executable permissions: {e}"))?;
+    }
+
+    let _ = fs::remove_file(&staged);
+    Ok(())
+}